@@ -0,0 +1,174 @@
+//! The rasterizer-backed render pipeline: a framebuffer plus a `draw` call that runs a vertex
+//! shader over a model's faces and a pixel shader over the triangles they rasterize to.
+
+use cgmath::*;
+use image::DynamicImage;
+use std::path::Path;
+use std::sync::Arc;
+use color;
+use model;
+use triangle;
+use utils;
+
+
+/// Same mapping as `utils::screen_to_image_space`, but keeps the fractional pixel position
+/// instead of rounding to a whole pixel. `Gl::draw` needs this: its vertices feed straight into
+/// `triangle`'s fixed-point subpixel rasterizer, and rounding here first would throw away the
+/// sub-pixel precision that rasterizer exists to use, leaving vertices snapped to whole pixels
+/// before `snap_vertex`/`EdgeWalk` ever see them.
+fn screen_to_image_space_f32(x: f32, y: f32, width: u32, height: u32) -> Vector2<f32> {
+    Vector2::new(
+        (x + 1.0) * width as f32 / 2.0,
+        (1.0 - y) * height as f32 / 2.0,
+    )
+}
+
+/// Builds a right-handed view matrix looking from `eye` towards `target`.
+pub fn view_matrix(eye: Vector3<f32>, target: Vector3<f32>, up: Vector3<f32>) -> Matrix4<f32> {
+    let z = (eye - target).normalize();
+    let x = up.cross(z).normalize();
+    let y = z.cross(x).normalize();
+    Matrix4::new(
+        x.x,
+        y.x,
+        z.x,
+        0.0,
+        x.y,
+        y.y,
+        z.y,
+        0.0,
+        x.z,
+        y.z,
+        z.z,
+        0.0,
+        -x.dot(eye),
+        -y.dot(eye),
+        -z.dot(eye),
+        1.0,
+    )
+}
+
+
+/// Per-draw-call vertex shader inputs.
+#[derive(Clone, Copy)]
+pub struct VSInput {
+    pub view: Matrix4<f32>,
+    pub projection: Matrix4<f32>,
+    pub camera: Vector3<f32>,
+    pub camera_target: Vector3<f32>,
+}
+
+impl Default for VSInput {
+    fn default() -> VSInput {
+        VSInput {
+            view: Matrix4::identity(),
+            projection: Matrix4::identity(),
+            camera: Vector3::new(0.0, 0.0, 0.0),
+            camera_target: Vector3::new(0.0, 0.0, -1.0),
+        }
+    }
+}
+
+/// Pixel shader inputs: constant state set once per draw call (`textures`, `light_pos`,
+/// `cam_dir`) plus the per-pixel varyings (`position`, `normal`, `uv`, `barycentric`) that
+/// `Gl::draw` fills in for every rasterized pixel before invoking the shader.
+#[derive(Clone)]
+pub struct PSInput {
+    pub textures: Vec<Arc<DynamicImage>>,
+    pub light_pos: Vector3<f32>,
+    pub cam_dir: Vector3<f32>,
+    pub position: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    pub uv: Vector2<f32>,
+    pub barycentric: Vector3<f32>,
+}
+
+impl Default for PSInput {
+    fn default() -> PSInput {
+        PSInput {
+            textures: Vec::new(),
+            light_pos: Vector3::new(0.0, 0.0, 0.0),
+            cam_dir: Vector3::new(0.0, 0.0, 1.0),
+            position: Vector3::new(0.0, 0.0, 0.0),
+            normal: Vector3::new(0.0, 0.0, 1.0),
+            uv: Vector2::new(0.0, 0.0),
+            barycentric: Vector3::new(1.0, 0.0, 0.0),
+        }
+    }
+}
+
+
+/// The rasterizer: owns a framebuffer and an optional scissor rectangle that every triangle
+/// submitted to `draw` is clipped against.
+pub struct Gl {
+    width: u32,
+    height: u32,
+    framebuffer: Vec<u32>,
+    scissor: Option<(u32, u32, u32, u32)>,
+}
+
+impl Gl {
+    pub fn new(width: u32, height: u32) -> Gl {
+        Gl {
+            width: width,
+            height: height,
+            framebuffer: vec![0; (width * height) as usize],
+            scissor: None,
+        }
+    }
+
+    /// Restricts rasterization to `[min_x, max_x) x [min_y, max_y)`; `draw` intersects this
+    /// with each triangle's bounding box (and always with the framebuffer bounds) before
+    /// traversing it, so nothing outside the region is ever tested or written. Pass
+    /// `clear_scissor` to go back to rendering the whole framebuffer.
+    pub fn set_scissor(&mut self, min_x: u32, min_y: u32, max_x: u32, max_y: u32) {
+        self.scissor = Some((min_x, min_y, max_x, max_y));
+    }
+
+    pub fn clear_scissor(&mut self) {
+        self.scissor = None;
+    }
+
+    pub fn draw(
+        &mut self,
+        model: &model::Model,
+        vertex_shader: fn(&VSInput, Vector3<f32>) -> Vector3<f32>,
+        vs_in: VSInput,
+        pixel_shader: fn(PSInput) -> color::Color,
+        ps_in: PSInput,
+    ) {
+        let buffer_width = self.width as usize;
+
+        for face in &model.faces {
+            let screen: Vec<Vector2<f32>> = face
+                .verts
+                .iter()
+                .map(|vert| {
+                    let transformed = vertex_shader(&vs_in, vert.pos);
+                    screen_to_image_space_f32(transformed.x, transformed.y, self.width, self.height)
+                })
+                .collect();
+
+            let triangle_iter =
+                triangle::TriangleIterator::new(&screen, self.width, self.height, self.scissor);
+            for row in triangle_iter {
+                for (x, y, weights) in row {
+                    let mut pixel_in = ps_in.clone();
+                    pixel_in.position = face.verts[0].pos * weights.x +
+                        face.verts[1].pos * weights.y + face.verts[2].pos * weights.z;
+                    pixel_in.normal = face.verts[0].normal * weights.x +
+                        face.verts[1].normal * weights.y + face.verts[2].normal * weights.z;
+                    pixel_in.uv = face.verts[0].uv * weights.x + face.verts[1].uv * weights.y +
+                        face.verts[2].uv * weights.z;
+                    pixel_in.barycentric = weights;
+                    let color = pixel_shader(pixel_in);
+                    self.framebuffer[utils::xy(x, y, buffer_width)] = color.bgra();
+                }
+            }
+        }
+    }
+
+    pub fn save_framebuffer_as_image(&self, path: &Path) {
+        utils::save_buffer_as_image(path, &self.framebuffer, self.width, self.height);
+    }
+}
@@ -0,0 +1,371 @@
+//! Alternative render path that traces primary rays against a BVH over a `model::Model`,
+//! instead of scan-converting triangles. Shares the model and shader inputs with the
+//! rasterizer (`gl`) so a scene can be rendered either way.
+
+use cgmath::*;
+use color;
+use gl;
+use model;
+use utils;
+
+
+#[derive(Clone, Copy)]
+struct Aabb {
+    min: Vector3<f32>,
+    max: Vector3<f32>,
+}
+
+impl Aabb {
+    fn empty() -> Aabb {
+        Aabb {
+            min: Vector3::new(::std::f32::MAX, ::std::f32::MAX, ::std::f32::MAX),
+            max: Vector3::new(::std::f32::MIN, ::std::f32::MIN, ::std::f32::MIN),
+        }
+    }
+
+    fn grow(&mut self, p: Vector3<f32>) {
+        self.min.x = self.min.x.min(p.x);
+        self.min.y = self.min.y.min(p.y);
+        self.min.z = self.min.z.min(p.z);
+        self.max.x = self.max.x.max(p.x);
+        self.max.y = self.max.y.max(p.y);
+        self.max.z = self.max.z.max(p.z);
+    }
+
+    fn union(&self, other: &Aabb) -> Aabb {
+        let mut result = *self;
+        result.grow(other.min);
+        result.grow(other.max);
+        result
+    }
+
+    fn centroid(&self) -> Vector3<f32> {
+        (self.min + self.max) / 2.0
+    }
+
+    fn longest_axis(&self) -> usize {
+        let extent = self.max - self.min;
+        if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn axis(&self, axis: usize) -> f32 {
+        match axis {
+            0 => self.centroid().x,
+            1 => self.centroid().y,
+            _ => self.centroid().z,
+        }
+    }
+
+    /// Slab test: returns whether the ray intersects this box before `t_max`.
+    fn hit(&self, ray: &Ray, t_max: f32) -> bool {
+        let mut t_min = 0.0f32;
+        let mut t_max = t_max;
+        for axis in 0..3 {
+            let (bounds_min, bounds_max) = (self.min[axis], self.max[axis]);
+            let (near, far) = if ray.sign[axis] {
+                (bounds_max, bounds_min)
+            } else {
+                (bounds_min, bounds_max)
+            };
+            let t_near = (near - ray.origin[axis]) * ray.inv_dir[axis];
+            let t_far = (far - ray.origin[axis]) * ray.inv_dir[axis];
+            t_min = t_min.max(t_near);
+            t_max = t_max.min(t_far);
+            if t_min > t_max {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+struct Ray {
+    origin: Vector3<f32>,
+    dir: Vector3<f32>,
+    inv_dir: Vector3<f32>,
+    /// Per-axis sign of `inv_dir`, precomputed once per ray and used to pick the near/far slab.
+    sign: [bool; 3],
+}
+
+impl Ray {
+    fn new(origin: Vector3<f32>, dir: Vector3<f32>) -> Ray {
+        let inv_dir = Vector3::new(1.0 / dir.x, 1.0 / dir.y, 1.0 / dir.z);
+        Ray {
+            origin: origin,
+            dir: dir,
+            inv_dir: inv_dir,
+            sign: [inv_dir.x < 0.0, inv_dir.y < 0.0, inv_dir.z < 0.0],
+        }
+    }
+}
+
+const MOLLER_TRUMBORE_EPSILON: f32 = 1e-6;
+
+/// Möller–Trumbore ray/triangle intersection. Returns `(t, u, v)` on hit, where `u`/`v` are two
+/// of the three barycentric weights (the third is `1 - u - v`).
+fn intersect_triangle(
+    ray: &Ray,
+    v0: Vector3<f32>,
+    v1: Vector3<f32>,
+    v2: Vector3<f32>,
+) -> Option<(f32, f32, f32)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let h = ray.dir.cross(edge2);
+    let a = edge1.dot(h);
+    if a.abs() < MOLLER_TRUMBORE_EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / a;
+    let s = ray.origin - v0;
+    let u = f * s.dot(h);
+    if u < 0.0 || u > 1.0 {
+        return None;
+    }
+
+    let q = s.cross(edge1);
+    let v = f * ray.dir.dot(q);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = f * edge2.dot(q);
+    if t > MOLLER_TRUMBORE_EPSILON {
+        Some((t, u, v))
+    } else {
+        None
+    }
+}
+
+/// `Bvh::intersect` bottoms out in `intersect_triangle` at every leaf; this checks that leaf
+/// test on its own, with rays known to hit (at known `t, u, v`) or miss a given triangle. The
+/// BVH traversal itself is not exercised here.
+#[test]
+fn test_intersect_triangle_hit_and_miss() {
+    let v0 = Vector3::new(0.0, 0.0, 0.0);
+    let v1 = Vector3::new(1.0, 0.0, 0.0);
+    let v2 = Vector3::new(0.0, 1.0, 0.0);
+
+    let hit = Ray::new(Vector3::new(0.25, 0.25, 1.0), Vector3::new(0.0, 0.0, -1.0));
+    let (t, u, v) = intersect_triangle(&hit, v0, v1, v2).expect("ray should hit the triangle");
+    assert!((t - 1.0).abs() < 1e-5);
+    assert!((u - 0.25).abs() < 1e-5);
+    assert!((v - 0.25).abs() < 1e-5);
+
+    let miss = Ray::new(Vector3::new(5.0, 5.0, 1.0), Vector3::new(0.0, 0.0, -1.0));
+    assert!(intersect_triangle(&miss, v0, v1, v2).is_none());
+
+    let behind = Ray::new(Vector3::new(0.25, 0.25, -1.0), Vector3::new(0.0, 0.0, -1.0));
+    assert!(intersect_triangle(&behind, v0, v1, v2).is_none());
+}
+
+
+struct BvhNode {
+    bounds: Aabb,
+    /// Range `[start, end)` into `Bvh::faces` covered by this node.
+    start: usize,
+    end: usize,
+    /// Indices into `Bvh::nodes`; `None` for a leaf.
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+impl BvhNode {
+    fn is_leaf(&self) -> bool {
+        self.left.is_none()
+    }
+}
+
+/// Bounding-volume hierarchy over a model's faces, split on the longest axis at the median
+/// centroid of each node's faces.
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    /// Face indices into the source model, reordered so each node owns a contiguous range.
+    faces: Vec<usize>,
+}
+
+const BVH_LEAF_FACES: usize = 4;
+
+impl Bvh {
+    pub fn build(model: &model::Model) -> Bvh {
+        let bounds: Vec<Aabb> = model
+            .faces
+            .iter()
+            .map(|face| {
+                let mut bb = Aabb::empty();
+                for vert in &face.verts {
+                    bb.grow(vert.pos);
+                }
+                bb
+            })
+            .collect();
+
+        let mut faces: Vec<usize> = (0..model.faces.len()).collect();
+        let mut nodes = Vec::new();
+        if !faces.is_empty() {
+            Bvh::build_node(&mut nodes, &bounds, &mut faces, 0, faces.len());
+        }
+        Bvh {
+            nodes: nodes,
+            faces: faces,
+        }
+    }
+
+    /// Builds the node covering `faces[start..end]` (splitting recursively) and returns its
+    /// index in `nodes`.
+    fn build_node(
+        nodes: &mut Vec<BvhNode>,
+        bounds: &[Aabb],
+        faces: &mut [usize],
+        start: usize,
+        end: usize,
+    ) -> usize {
+        let mut node_bounds = Aabb::empty();
+        for &face in &faces[start..end] {
+            node_bounds = node_bounds.union(&bounds[face]);
+        }
+
+        if end - start <= BVH_LEAF_FACES {
+            nodes.push(BvhNode {
+                bounds: node_bounds,
+                start: start,
+                end: end,
+                left: None,
+                right: None,
+            });
+            return nodes.len() - 1;
+        }
+
+        let axis = node_bounds.longest_axis();
+        faces[start..end].sort_by(|&a, &b| {
+            bounds[a]
+                .axis(axis)
+                .partial_cmp(&bounds[b].axis(axis))
+                .unwrap()
+        });
+        let mid = start + (end - start) / 2;
+
+        let left = Bvh::build_node(nodes, bounds, faces, start, mid);
+        let right = Bvh::build_node(nodes, bounds, faces, mid, end);
+
+        nodes.push(BvhNode {
+            bounds: node_bounds,
+            start: start,
+            end: end,
+            left: Some(left),
+            right: Some(right),
+        });
+        nodes.len() - 1
+    }
+
+    /// Nearest hit along `ray`, as `(face index into the model, t, u, v)`.
+    fn intersect(&self, model: &model::Model, ray: &Ray) -> Option<(usize, f32, f32, f32)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut closest: Option<(usize, f32, f32, f32)> = None;
+        self.intersect_node(model, ray, self.nodes.len() - 1, &mut closest);
+        closest
+    }
+
+    fn intersect_node(
+        &self,
+        model: &model::Model,
+        ray: &Ray,
+        node_index: usize,
+        closest: &mut Option<(usize, f32, f32, f32)>,
+    ) {
+        let node = &self.nodes[node_index];
+        let t_max = closest.map(|(_, t, _, _)| t).unwrap_or(::std::f32::MAX);
+        if !node.bounds.hit(ray, t_max) {
+            return;
+        }
+
+        if node.is_leaf() {
+            for &face_index in &self.faces[node.start..node.end] {
+                let face = &model.faces[face_index];
+                let hit = intersect_triangle(
+                    ray,
+                    face.verts[0].pos,
+                    face.verts[1].pos,
+                    face.verts[2].pos,
+                );
+                if let Some((t, u, v)) = hit {
+                    let better = closest.map(|(_, best_t, _, _)| t < best_t).unwrap_or(true);
+                    if better {
+                        *closest = Some((face_index, t, u, v));
+                    }
+                }
+            }
+            return;
+        }
+
+        if let Some(left) = node.left {
+            self.intersect_node(model, ray, left, closest);
+        }
+        if let Some(right) = node.right {
+            self.intersect_node(model, ray, right, closest);
+        }
+    }
+}
+
+
+/// Unprojects an NDC point back to world space through the inverse view-projection matrix,
+/// dividing by `w` to undo the perspective projection.
+fn unproject(inv_view_projection: &Matrix4<f32>, ndc_x: f32, ndc_y: f32, ndc_z: f32) -> Vector3<f32> {
+    let clip = Vector4::new(ndc_x, ndc_y, ndc_z, 1.0);
+    let world = inv_view_projection * clip;
+    world.truncate() / world.w
+}
+
+/// Ray-traces `model` through the camera/view/projection set up in `vs_in`, shading each hit
+/// with `pixel_shader` (e.g. `shaders::spec_pixel`) fed the hit's barycentric weights.
+pub fn draw(
+    model: &model::Model,
+    bvh: &Bvh,
+    vs_in: &gl::VSInput,
+    pixel_shader: fn(gl::PSInput) -> color::Color,
+    ps_in: gl::PSInput,
+    buffer: &mut [u32],
+    buffer_width: usize,
+    buffer_height: usize,
+) {
+    let origin = vs_in.camera;
+    let view_projection = vs_in.projection * vs_in.view;
+    let inv_view_projection = view_projection.invert().expect(
+        "camera view/projection matrix must be invertible to unproject pixel rays",
+    );
+
+    for y in 0..buffer_height {
+        for x in 0..buffer_width {
+            let ndc_x = 2.0 * ((x as f32 + 0.5) / buffer_width as f32) - 1.0;
+            let ndc_y = 1.0 - 2.0 * ((y as f32 + 0.5) / buffer_height as f32);
+
+            let aim = unproject(&inv_view_projection, ndc_x, ndc_y, -1.0);
+            let dir = (aim - origin).normalize();
+            let ray = Ray::new(origin, dir);
+
+            if let Some((face_index, _, u, v)) = bvh.intersect(model, &ray) {
+                let weights = Vector3::new(1.0 - u - v, u, v);
+                let face = &model.faces[face_index];
+                let mut ps_in = ps_in.clone();
+                ps_in.position = face.verts[0].pos * weights.x + face.verts[1].pos * weights.y +
+                    face.verts[2].pos * weights.z;
+                ps_in.normal = face.verts[0].normal * weights.x +
+                    face.verts[1].normal * weights.y + face.verts[2].normal * weights.z;
+                ps_in.uv = face.verts[0].uv * weights.x + face.verts[1].uv * weights.y +
+                    face.verts[2].uv * weights.z;
+                ps_in.barycentric = weights;
+                let color = pixel_shader(ps_in);
+                buffer[utils::xy(x, y, buffer_width)] = color.bgra();
+            }
+        }
+    }
+}
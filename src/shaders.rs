@@ -0,0 +1,258 @@
+//! Vertex and pixel shaders run by `gl::Gl::draw`. Shaders are plain functions with a fixed
+//! signature so they can be passed around as `fn` pointers; all per-draw state (transforms,
+//! lights, textures) travels through `VSInput`/`PSInput`.
+
+use cgmath::*;
+use image::GenericImage;
+use std::sync::Arc;
+use color;
+use gl;
+
+
+fn sample_texture(texture: &Arc<image::DynamicImage>, uv: Vector2<f32>) -> Vector3<f32> {
+    let (width, height) = texture.dimensions();
+    let x = (uv.x.fract().abs() * width as f32) as u32 % width.max(1);
+    let y = ((1.0 - uv.y.fract().abs()) * height as f32) as u32 % height.max(1);
+    let pixel = texture.get_pixel(x, y);
+    Vector3::new(
+        pixel.data[0] as f32 / 255.0,
+        pixel.data[1] as f32 / 255.0,
+        pixel.data[2] as f32 / 255.0,
+    )
+}
+
+fn to_color(rgb: Vector3<f32>) -> color::Color {
+    color::Color::new(
+        (rgb.x.min(1.0).max(0.0) * 255.0) as u8,
+        (rgb.y.min(1.0).max(0.0) * 255.0) as u8,
+        (rgb.z.min(1.0).max(0.0) * 255.0) as u8,
+    )
+}
+
+
+/// Transforms a vertex position with the view/projection set up in `vs_in`.
+pub fn simple_vertex(vs_in: &gl::VSInput, position: Vector3<f32>) -> Vector3<f32> {
+    let clip = vs_in.projection * vs_in.view * position.extend(1.0);
+    Vector3::new(clip.x, clip.y, clip.z)
+}
+
+/// Lambertian diffuse only, sampling albedo from `textures[0]`.
+pub fn simple_pixel(ps_in: gl::PSInput) -> color::Color {
+    let n = ps_in.normal.normalize();
+    let l = (ps_in.light_pos - ps_in.position).normalize();
+    let albedo = sample_texture(&ps_in.textures[0], ps_in.uv);
+    to_color(albedo * n.dot(l).max(0.0))
+}
+
+/// Phong diffuse + specular, sampling albedo from `textures[0]` and a shininess exponent from
+/// `textures[2]`.
+pub fn spec_pixel(ps_in: gl::PSInput) -> color::Color {
+    let n = ps_in.normal.normalize();
+    let l = (ps_in.light_pos - ps_in.position).normalize();
+    let v = ps_in.cam_dir.normalize();
+    let r = (n * 2.0 * n.dot(l) - l).normalize();
+
+    let albedo = sample_texture(&ps_in.textures[0], ps_in.uv);
+    let shininess = sample_texture(&ps_in.textures[2], ps_in.uv).x * 255.0;
+
+    let diffuse = albedo * n.dot(l).max(0.0);
+    let specular = r.dot(v).max(0.0).powf(shininess.max(1.0));
+
+    to_color(diffuse + Vector3::new(specular, specular, specular))
+}
+
+
+/// Oren-Nayar rough-diffuse reflectance for roughness `sigma` (radians), given the surface
+/// normal `n`, light direction `l` and view direction `v` (all normalized, pointing away from
+/// the surface), modulated by `albedo`.
+pub fn oren_nayar_diffuse(
+    n: Vector3<f32>,
+    l: Vector3<f32>,
+    v: Vector3<f32>,
+    sigma: f32,
+    albedo: Vector3<f32>,
+) -> Vector3<f32> {
+    let n_dot_l = n.dot(l).min(1.0).max(0.0);
+    let n_dot_v = n.dot(v).min(1.0).max(0.0);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+        return Vector3::new(0.0, 0.0, 0.0);
+    }
+
+    let sigma2 = sigma * sigma;
+    let a = 1.0 - 0.5 * sigma2 / (sigma2 + 0.33);
+    let b = 0.45 * sigma2 / (sigma2 + 0.09);
+
+    // n_dot_l/n_dot_v are clamped to [0, 1] above: acos of anything over 1.0 is NaN, and two
+    // independently-normalized near-parallel vectors routinely dot to a hair over 1.0 in f32.
+    let theta_i = n_dot_l.acos();
+    let theta_r = n_dot_v.acos();
+    let alpha = theta_i.max(theta_r);
+    let beta = theta_i.min(theta_r);
+
+    // Azimuth term: angle between the light and view directions projected onto the tangent
+    // plane, via the component of each direction orthogonal to the normal. That projection is
+    // the zero vector (azimuth undefined) whenever L or V is exactly aligned with N, so guard
+    // the normalize instead of letting it hand back NaN.
+    let l_tangent = l - n * n_dot_l;
+    let v_tangent = v - n * n_dot_v;
+    let cos_phi_diff = if l_tangent.magnitude2() > 1e-8 && v_tangent.magnitude2() > 1e-8 {
+        l_tangent.normalize().dot(v_tangent.normalize()).max(0.0)
+    } else {
+        0.0
+    };
+
+    let reflectance = a + b * cos_phi_diff * alpha.sin() * beta.tan();
+    albedo * n_dot_l * reflectance
+}
+
+/// GGX normal distribution function: the fraction of microfacets aligned with half-vector `h`.
+fn ggx_distribution(n: Vector3<f32>, h: Vector3<f32>, roughness: f32) -> f32 {
+    let alpha2 = (roughness * roughness).max(1e-4);
+    let n_dot_h = n.dot(h).max(0.0);
+    let denom = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    alpha2 / (::std::f32::consts::PI * denom * denom).max(1e-6)
+}
+
+/// Schlick-Beckmann/Smith geometric shadowing-masking term.
+fn smith_geometry(n: Vector3<f32>, v: Vector3<f32>, l: Vector3<f32>, roughness: f32) -> f32 {
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let g1 = |n_dot_x: f32| n_dot_x / (n_dot_x * (1.0 - k) + k);
+    g1(n.dot(v).max(0.0)) * g1(n.dot(l).max(0.0))
+}
+
+/// Schlick's approximation of the Fresnel reflectance at grazing angle `v_dot_h`, for a surface
+/// with normal-incidence reflectance `f0`.
+fn schlick_fresnel(f0: Vector3<f32>, v_dot_h: f32) -> Vector3<f32> {
+    f0 + (Vector3::new(1.0, 1.0, 1.0) - f0) * (1.0 - v_dot_h).max(0.0).powi(5)
+}
+
+/// Cook-Torrance specular term `D * F * G / (4 * NdotL * NdotV)`.
+pub fn cook_torrance_specular(
+    n: Vector3<f32>,
+    l: Vector3<f32>,
+    v: Vector3<f32>,
+    roughness: f32,
+    f0: Vector3<f32>,
+) -> Vector3<f32> {
+    let n_dot_l = n.dot(l).max(0.0);
+    let n_dot_v = n.dot(v).max(0.0);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+        return Vector3::new(0.0, 0.0, 0.0);
+    }
+
+    let h = (l + v).normalize();
+    let d = ggx_distribution(n, h, roughness);
+    let g = smith_geometry(n, v, l, roughness);
+    let f = schlick_fresnel(f0, v.dot(h).max(0.0));
+
+    f * (d * g / (4.0 * n_dot_l * n_dot_v))
+}
+
+/// Physically based pixel shader combining Oren-Nayar diffuse and Cook-Torrance specular,
+/// driving roughness and `F0` from the specular texture slot (`textures[2]`): its red channel
+/// is roughness, its green channel `F0`.
+pub fn pbr_pixel(ps_in: gl::PSInput) -> color::Color {
+    let n = ps_in.normal.normalize();
+    let l = (ps_in.light_pos - ps_in.position).normalize();
+    let v = ps_in.cam_dir.normalize();
+
+    let albedo = sample_texture(&ps_in.textures[0], ps_in.uv);
+    let material = sample_texture(&ps_in.textures[2], ps_in.uv);
+    let roughness = material.x.max(0.04);
+    let f0 = Vector3::new(material.y, material.y, material.y);
+
+    let diffuse = oren_nayar_diffuse(n, l, v, roughness, albedo);
+    let specular = cook_torrance_specular(n, l, v, roughness, f0);
+
+    to_color(diffuse + specular)
+}
+
+#[test]
+fn test_oren_nayar_diffuse_back_facing_is_zero() {
+    let n = Vector3::new(0.0, 0.0, 1.0);
+    let albedo = Vector3::new(1.0, 1.0, 1.0);
+    let v = Vector3::new(0.0, 0.0, 1.0);
+
+    let l_behind = Vector3::new(0.0, 0.0, -1.0);
+    assert_eq!(
+        oren_nayar_diffuse(n, l_behind, v, 0.5, albedo),
+        Vector3::new(0.0, 0.0, 0.0)
+    );
+
+    let v_behind = Vector3::new(0.0, 0.0, -1.0);
+    let l = Vector3::new(0.0, 0.0, 1.0);
+    assert_eq!(
+        oren_nayar_diffuse(n, l, v_behind, 0.5, albedo),
+        Vector3::new(0.0, 0.0, 0.0)
+    );
+}
+
+#[test]
+fn test_oren_nayar_diffuse_normal_incidence_is_plausible() {
+    let n = Vector3::new(0.0, 0.0, 1.0);
+    let l = Vector3::new(0.0, 0.0, 1.0);
+    let v = Vector3::new(0.0, 0.0, 1.0);
+    let albedo = Vector3::new(1.0, 1.0, 1.0);
+
+    let result = oren_nayar_diffuse(n, l, v, 0.5, albedo);
+    assert!(result.x > 0.0 && result.x <= 1.0);
+}
+
+/// Two independently-normalized near-parallel vectors routinely dot to a hair over 1.0 in f32;
+/// `n`/`l` below are one such pair (`n.dot(l) == 1.0000001192...`), which used to feed `acos` a
+/// value outside its domain and poison the result with NaN.
+#[test]
+fn test_oren_nayar_diffuse_near_parallel_normal_and_light_is_finite() {
+    let n = Vector3::new(-0.7434368, -0.26805755, 0.61273724);
+    let l = Vector3::new(-0.74343675, -0.26805782, 0.61273724);
+    let v = Vector3::new(0.0, 0.0, 1.0);
+    let albedo = Vector3::new(1.0, 1.0, 1.0);
+    assert!(n.dot(l) > 1.0);
+
+    let result = oren_nayar_diffuse(n, l, v, 0.5, albedo);
+    assert!(result.x.is_finite() && result.x >= 0.0);
+}
+
+/// `L` on-axis with `N` (`V` at 45 degrees) zeroes the light-tangent projection, which used to feed
+/// a zero vector into `normalize()` and poison the result with NaN.
+#[test]
+fn test_oren_nayar_diffuse_light_on_axis_is_finite() {
+    let n = Vector3::new(0.0, 0.0, 1.0);
+    let l = Vector3::new(0.0, 0.0, 1.0);
+    let v = Vector3::new(1.0, 0.0, 1.0).normalize();
+    let albedo = Vector3::new(1.0, 1.0, 1.0);
+
+    let result = oren_nayar_diffuse(n, l, v, 0.5, albedo);
+    assert!(result.x.is_finite() && result.x >= 0.0);
+}
+
+#[test]
+fn test_cook_torrance_specular_back_facing_is_zero() {
+    let n = Vector3::new(0.0, 0.0, 1.0);
+    let f0 = Vector3::new(0.04, 0.04, 0.04);
+    let v = Vector3::new(0.0, 0.0, 1.0);
+
+    let l_behind = Vector3::new(0.0, 0.0, -1.0);
+    assert_eq!(
+        cook_torrance_specular(n, l_behind, v, 0.5, f0),
+        Vector3::new(0.0, 0.0, 0.0)
+    );
+
+    let v_behind = Vector3::new(0.0, 0.0, -1.0);
+    let l = Vector3::new(0.0, 0.0, 1.0);
+    assert_eq!(
+        cook_torrance_specular(n, l, v_behind, 0.5, f0),
+        Vector3::new(0.0, 0.0, 0.0)
+    );
+}
+
+#[test]
+fn test_cook_torrance_specular_normal_incidence_is_plausible() {
+    let n = Vector3::new(0.0, 0.0, 1.0);
+    let l = Vector3::new(0.0, 0.0, 1.0);
+    let v = Vector3::new(0.0, 0.0, 1.0);
+    let f0 = Vector3::new(0.04, 0.04, 0.04);
+
+    let result = cook_torrance_specular(n, l, v, 0.5, f0);
+    assert!(result.x.is_finite() && result.x > 0.0);
+}
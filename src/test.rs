@@ -13,6 +13,7 @@ use color;
 use utils;
 use line;
 use triangle;
+use raytrace;
 
 const WINDOW_WIDTH: u32 = 512;
 const WINDOW_HEIGHT: u32 = 512;
@@ -112,11 +113,20 @@ fn bench_triangle(b: &mut Bencher) {
     let mut fb: Vec<u32> = vec![0; (WINDOW_WIDTH * WINDOW_HEIGHT) as usize];
     let fb_width = WINDOW_WIDTH as usize;
     let color = color::Color::red();
-    let mut tri: Vec<Vector2<u32>> = Vec::with_capacity(3);
-    tri.push(Vector2::<u32>::new(0, 0));
-    tri.push(Vector2::<u32>::new(0, WINDOW_HEIGHT));
-    tri.push(Vector2::<u32>::new(WINDOW_WIDTH, WINDOW_HEIGHT));
-    b.iter(|| triangle::draw(&tri, color, &mut fb, fb_width));
+    let mut tri: Vec<Vector2<f32>> = Vec::with_capacity(3);
+    tri.push(Vector2::<f32>::new(0.0, 0.0));
+    tri.push(Vector2::<f32>::new(0.0, WINDOW_HEIGHT as f32));
+    tri.push(Vector2::<f32>::new(WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32));
+    b.iter(|| {
+        triangle::draw(
+            &tri,
+            color,
+            &mut fb,
+            fb_width,
+            WINDOW_HEIGHT as usize,
+            None,
+        )
+    });
     utils::save_buffer_as_image(
         Path::new("./test_output/bench_triangle.png"),
         &fb,
@@ -130,13 +140,16 @@ fn bench_triangle_iter(b: &mut Bencher) {
     let mut fb: Vec<u32> = vec![0; (WINDOW_WIDTH * WINDOW_HEIGHT) as usize];
     let fb_width = WINDOW_WIDTH as usize;
     let color = color::Color::red();
-    let mut tri: Vec<Vector2<u32>> = Vec::with_capacity(3);
-    tri.push(Vector2::<u32>::new(0, 0));
-    tri.push(Vector2::<u32>::new(0, WINDOW_HEIGHT - 1));
-    tri.push(Vector2::<u32>::new(WINDOW_WIDTH - 1, WINDOW_HEIGHT - 1));
+    let mut tri: Vec<Vector2<f32>> = Vec::with_capacity(3);
+    tri.push(Vector2::<f32>::new(0.0, 0.0));
+    tri.push(Vector2::<f32>::new(0.0, (WINDOW_HEIGHT - 1) as f32));
+    tri.push(Vector2::<f32>::new(
+        (WINDOW_WIDTH - 1) as f32,
+        (WINDOW_HEIGHT - 1) as f32,
+    ));
 
     b.iter(|| {
-        let triangle = triangle::TriangleIterator::new(&tri);
+        let triangle = triangle::TriangleIterator::new(&tri, WINDOW_WIDTH, WINDOW_HEIGHT, None);
         for line in triangle {
             for point in line {
                 fb[utils::xy(point.0, point.1, fb_width)] = color.bgra();
@@ -229,6 +242,120 @@ fn test_head() {
     graphics.save_framebuffer_as_image(Path::new("./test_output/test_head.png"));
 }
 
+/// `shaders::pbr_pixel` was only ever unit-tested on `oren_nayar_diffuse`/`cook_torrance_specular`
+/// in isolation; render the head model through it so a PBR pixel shader is actually selected
+/// against real textures/geometry. The image is saved for inspection; its contents are not
+/// asserted.
+#[test]
+fn test_head_pbr() {
+    let mut graphics: gl::Gl = gl::Gl::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+
+    let camera: Vector3<f32> = Vector3::new(2.0, 0.0, 3.0);
+    let camera_target: Vector3<f32> = Vector3::new(0.0, 0.0, 0.0);
+    let up: Vector3<f32> = Vector3::new(0.0, 1.0, 0.0);
+    let light_pos = Vector3::new(0.0, 0.0, 1.0);
+
+    let view = gl::view_matrix(camera, camera_target, up);
+    let mut projection: Matrix4<f32> = Matrix4::identity();
+    projection[2][3] = -0.5 / camera.z;
+
+    let head_modelpath = Path::new("./content/african_head/african_head.obj");
+    let head_model = model::Model::load(head_modelpath).unwrap();
+
+    let head_diffuse_image = image::open("./content/african_head/african_head_diffuse.tga")
+        .unwrap();
+    let head_diffuse_tex = sync::Arc::new(head_diffuse_image);
+    let head_normals_image = image::open("./content/african_head/african_head_nm.tga").unwrap();
+    let head_normals_tex = sync::Arc::new(head_normals_image);
+    let head_specular_image = image::open("./content/african_head/african_head_spec.tga").unwrap();
+    let head_specular_tex = sync::Arc::new(head_specular_image);
+
+    let mut vs_in: gl::VSInput = gl::VSInput::default();
+    vs_in.view = view;
+    vs_in.projection = projection;
+    vs_in.camera = camera;
+    vs_in.camera_target = camera_target;
+
+    let mut ps_in: gl::PSInput = gl::PSInput::default();
+    ps_in.textures.push(head_diffuse_tex);
+    ps_in.textures.push(head_normals_tex);
+    ps_in.textures.push(head_specular_tex);
+    ps_in.light_pos = light_pos;
+    ps_in.cam_dir = camera - camera_target;
+
+    graphics.draw(
+        &head_model,
+        shaders::simple_vertex,
+        vs_in,
+        shaders::pbr_pixel,
+        ps_in,
+    );
+
+    graphics.save_framebuffer_as_image(Path::new("./test_output/test_head_pbr.png"));
+}
+
+/// `raytrace::draw` never gets exercised against real geometry otherwise: `Bvh`'s traversal,
+/// the camera-basis ray setup, and the barycentric hookup into a pixel shader all need a real
+/// model to prove they don't panic (degenerate rays, empty BVH nodes, ...) and actually produce
+/// an image, not just the isolated Möller-Trumbore unit test in `raytrace.rs`.
+#[test]
+fn test_head_raytraced() {
+    let camera: Vector3<f32> = Vector3::new(2.0, 0.0, 3.0);
+    let camera_target: Vector3<f32> = Vector3::new(0.0, 0.0, 0.0);
+    let up: Vector3<f32> = Vector3::new(0.0, 1.0, 0.0);
+    let light_pos = Vector3::new(0.0, 0.0, 1.0);
+
+    let view = gl::view_matrix(camera, camera_target, up);
+    let mut projection: Matrix4<f32> = Matrix4::identity();
+    projection[2][3] = -0.5 / camera.z;
+
+    let mut vs_in: gl::VSInput = gl::VSInput::default();
+    vs_in.view = view;
+    vs_in.projection = projection;
+    vs_in.camera = camera;
+    vs_in.camera_target = camera_target;
+
+    let head_modelpath = Path::new("./content/african_head/african_head.obj");
+    let head_model = model::Model::load(head_modelpath).unwrap();
+    let bvh = raytrace::Bvh::build(&head_model);
+
+    let head_diffuse_image = image::open("./content/african_head/african_head_diffuse.tga")
+        .unwrap();
+    let head_diffuse_tex = sync::Arc::new(head_diffuse_image);
+    let head_normals_image = image::open("./content/african_head/african_head_nm.tga").unwrap();
+    let head_normals_tex = sync::Arc::new(head_normals_image);
+    let head_specular_image = image::open("./content/african_head/african_head_spec.tga").unwrap();
+    let head_specular_tex = sync::Arc::new(head_specular_image);
+
+    let mut ps_in: gl::PSInput = gl::PSInput::default();
+    ps_in.textures.push(head_diffuse_tex);
+    ps_in.textures.push(head_normals_tex);
+    ps_in.textures.push(head_specular_tex);
+    ps_in.light_pos = light_pos;
+    ps_in.cam_dir = camera - camera_target;
+
+    let mut fb: Vec<u32> = vec![0; (WINDOW_WIDTH * WINDOW_HEIGHT) as usize];
+    raytrace::draw(
+        &head_model,
+        &bvh,
+        &vs_in,
+        shaders::spec_pixel,
+        ps_in,
+        &mut fb,
+        WINDOW_WIDTH as usize,
+        WINDOW_HEIGHT as usize,
+    );
+
+    assert!(fb.iter().any(|&pixel| pixel != 0), "raytraced head came out blank");
+
+    utils::save_buffer_as_image(
+        Path::new("./test_output/test_head_raytraced.png"),
+        &fb,
+        WINDOW_WIDTH,
+        WINDOW_HEIGHT,
+    );
+}
+
 fn _test_monkey() {
     let mut graphics: gl::Gl = gl::Gl::new(WINDOW_WIDTH, WINDOW_HEIGHT);
 
@@ -1,207 +1,520 @@
 use cgmath::*;
-use line;
 use color;
 use utils;
 
 
-/// Returns barycentric coordinates of point `point` in triangle `tri`.
-/// Triangle vertices positions are taken as Vector3 even though the function operates only in
-/// 2 dimensions for compatibility with rendering loops.
-pub fn barycentric(point: Vector2<f32>, tri: &[Vector3<f32>]) -> Option<Vector3<f32>> {
-    let u: Vector3<f32> =
-        Vector3::new(tri[2].x - tri[0].x, tri[1].x - tri[0].x, tri[0].x - point.x)
-            .cross(Vector3::new(
-                tri[2].y - tri[0].y,
-                tri[1].y - tri[0].y,
-                tri[0].y - point.y,
-            ));
-    if u.z.abs() < 1.0 {
-        None
-    } else {
-        let result = Vector3::<f32>::new(1.0 - (u.x + u.y) / u.z, u.y / u.z, u.x / u.z);
-        if result.x < 0.0 || result.y < 0.0 || result.z < 0.0 {
-            None
-        } else {
-            Some(result)
-        }
-    }
+/// Number of fractional bits used to represent a pixel in the rasterizer's fixed-point
+/// subpixel space, i.e. vertices are snapped to a grid of `1 / 2^SUBPIXEL_BITS` of a pixel.
+/// This is what lets two triangles that share an edge rasterize it without gaps or overdraw,
+/// instead of leaning on an epsilon fudge at integer pixel positions.
+pub const SUBPIXEL_BITS: i32 = 4;
+const SUBPIXEL_SCALE: i32 = 1 << SUBPIXEL_BITS;
+
+/// Snaps a float screen-space coordinate to the fixed-point subpixel grid.
+fn snap(v: f32) -> i32 {
+    (v * SUBPIXEL_SCALE as f32).round() as i32
+}
+
+fn snap_vertex(v: Vector2<f32>) -> Vector2<i32> {
+    Vector2::new(snap(v.x), snap(v.y))
 }
 
 
-/// Returns bounding box as tuple `(min_x, min_y, max_x, max_y)`
+/// Returns the pixel-space bounding box as tuple `(min_x, min_y, max_x, max_y)` that fully
+/// covers the given subpixel-space positions.
 /// # Panics
 /// * Not being able to find max or min value.
 /// * Or anything else really, full of unwrap.
-fn bounding_box(positions: &[Vector2<u32>]) -> (u32, u32, u32, u32) {
+fn bounding_box(positions: &[Vector2<i32>]) -> (i32, i32, i32, i32) {
     let min_x = positions.iter().map(|pos| pos.x).min().unwrap();
     let min_y = positions.iter().map(|pos| pos.y).min().unwrap();
     let max_x = positions.iter().map(|pos| pos.x).max().unwrap();
     let max_y = positions.iter().map(|pos| pos.y).max().unwrap();
-    (min_x, min_y, max_x, max_y)
+    (
+        min_x >> SUBPIXEL_BITS,
+        min_y >> SUBPIXEL_BITS,
+        (max_x + SUBPIXEL_SCALE - 1) >> SUBPIXEL_BITS,
+        (max_y + SUBPIXEL_SCALE - 1) >> SUBPIXEL_BITS,
+    )
+}
+
+/// Intersects a pixel-space bounding box with an optional scissor rectangle and with the
+/// framebuffer bounds `0..buffer_width, 0..buffer_height`, so nothing outside the visible,
+/// clipped region is ever tested or written. This is what keeps `utils::xy` safe to call even
+/// when a triangle projects partly (or fully) off-screen.
+fn clip_bounding_box(
+    bb: (i32, i32, i32, i32),
+    buffer_width: u32,
+    buffer_height: u32,
+    scissor: Option<(u32, u32, u32, u32)>,
+) -> (i32, i32, i32, i32) {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = bb;
+    if let Some((scissor_min_x, scissor_min_y, scissor_max_x, scissor_max_y)) = scissor {
+        min_x = min_x.max(scissor_min_x as i32);
+        min_y = min_y.max(scissor_min_y as i32);
+        max_x = max_x.min(scissor_max_x as i32);
+        max_y = max_y.min(scissor_max_y as i32);
+    }
+    min_x = min_x.max(0);
+    min_y = min_y.max(0);
+    max_x = max_x.min(buffer_width as i32);
+    max_y = max_y.min(buffer_height as i32);
+    (min_x, min_y, max_x.max(min_x), max_y.max(min_y))
 }
 
 
-fn naive_point_in_triangle(point: (usize, usize), triangle: &[Vector2<u32>]) -> bool {
-    let p0 = Vector2::<f32>::new(triangle[0].x as f32, triangle[0].y as f32);
-    let p1 = Vector2::<f32>::new(triangle[1].x as f32, triangle[1].y as f32);
-    let p2 = Vector2::<f32>::new(triangle[2].x as f32, triangle[2].y as f32);
-    let p = Vector2::<f32>::new(point.0 as f32, point.1 as f32);
+/// Twice the signed area of triangle `(a, b, p)`, i.e. the edge function of edge `a -> b`
+/// evaluated at `p`, in subpixel units. Positive on the left of the edge for a
+/// counter-clockwise winding.
+fn edge_function(a: Vector2<i32>, b: Vector2<i32>, p: Vector2<i32>) -> i64 {
+    (p.x - a.x) as i64 * (b.y - a.y) as i64 - (p.y - a.y) as i64 * (b.x - a.x) as i64
+}
 
-    let c0 = Vector3::<f32>::new(p2.x - p0.x, p1.x - p0.x, p0.x - p.x);
-    let c1 = Vector3::<f32>::new(p2.y - p0.y, p1.y - p0.y, p0.y - p.y);
-    let u = c0.cross(c1);
+/// Top-left fill rule: an edge "owns" the pixels that sit exactly on it only when it is a top
+/// edge (horizontal, pointing left) or a left edge (pointing down), so that two triangles
+/// sharing an edge never both rasterize the pixels on it.
+fn is_top_left_edge(from: Vector2<i32>, to: Vector2<i32>) -> bool {
+    let is_top = from.y == to.y && to.x < from.x;
+    let is_left = to.y > from.y;
+    is_top || is_left
+}
 
-    if u.z.abs() < 1.0 {
+/// Coverage test for a single pixel using the edge-function method, sampled at the pixel
+/// center. Kept as a straightforward, non-incremental reference; `draw` and `TriangleIterator`
+/// use a stepped version of the same math for the hot path.
+fn edge_coverage(triangle: &[Vector2<f32>], point: (u32, u32)) -> bool {
+    let a = snap_vertex(triangle[0]);
+    let b = snap_vertex(triangle[1]);
+    let c = snap_vertex(triangle[2]);
+    let p = Vector2::new(
+        (point.0 as i32) * SUBPIXEL_SCALE + SUBPIXEL_SCALE / 2,
+        (point.1 as i32) * SUBPIXEL_SCALE + SUBPIXEL_SCALE / 2,
+    );
+
+    let area = edge_function(a, b, c);
+    if area == 0 {
         return false;
     }
+    let (a, b, c) = if area < 0 { (a, c, b) } else { (a, b, c) };
 
-    let r = Vector3::<f32>::new(1.0 - (u.x + u.y) / u.z, u.y / u.z, u.x / u.z);
-
-    r.x > 0.0 && r.y > 0.0 && r.z > 0.0
-}
-
-const EPSILON: f32 = 0.01;
-const EPSILON_SQUARE: f32 = EPSILON * EPSILON;
-
-fn point_in_triangle_bounding_box(
-    x1: f32,
-    y1: f32,
-    x2: f32,
-    y2: f32,
-    x3: f32,
-    y3: f32,
-    point: (f32, f32),
-) -> bool {
-    let x = point.0;
-    let y = point.1;
-    let x_min: f32 = x1.min(x2.min(x3)) - EPSILON;
-    let x_max: f32 = x1.max(x2.max(x3)) + EPSILON;
-    let y_min: f32 = y1.min(y2.min(y3)) - EPSILON;
-    let y_max: f32 = y1.max(y2.max(y3)) + EPSILON;
-
-    !(x < x_min || x_max < x || y < y_min || y_max < y)
-}
-
-fn distance_square_point_to_segment(x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) -> f32 {
-    let p1_p2_square_length: f32 = (x2 - x1) * (x2 - x1) + (y2 - y1) * (y2 - y1);
-    let dot_product: f32 = ((x - x1) * (x2 - x1) + (y - y1) * (y2 - y1)) / p1_p2_square_length;
-    if dot_product < 0.0 {
-        (x - x1) * (x - x1) + (y - y1) * (y - y1)
-    } else if dot_product <= 1.0 {
-        let p_p1_square_length: f32 = (x1 - x) * (x1 - x) + (y1 - y) * (y1 - y);
-        p_p1_square_length - dot_product * dot_product * p1_p2_square_length
-    } else {
-        (x - x2) * (x - x2) + (y - y2) * (y - y2)
-    }
+    let edges = [(a, b), (b, c), (c, a)];
+    edges.iter().all(|&(from, to)| {
+        let w = edge_function(from, to, p);
+        w > 0 || (w == 0 && is_top_left_edge(from, to))
+    })
 }
 
-fn point_in_triangle(point: (usize, usize), triangle: &[Vector2<u32>]) -> bool {
-    let x1 = triangle[0].x as f32;
-    let y1 = triangle[0].y as f32;
-    let x2 = triangle[1].x as f32;
-    let y2 = triangle[1].y as f32;
-    let x3 = triangle[2].x as f32;
-    let y3 = triangle[2].y as f32;
-    let x = point.0 as f32;
-    let y = point.1 as f32;
+#[test]
+fn test_edge_coverage() {
+    let mut tri: Vec<Vector2<f32>> = Vec::with_capacity(3);
+    tri.push(Vector2::<f32>::new(245.0, 391.0));
+    tri.push(Vector2::<f32>::new(115.0, 200.0));
+    tri.push(Vector2::<f32>::new(306.0, 438.0));
 
-    if !point_in_triangle_bounding_box(x1, y1, x2, y2, x3, y3, (x, y)) {
-        return false;
-    }
+    assert!(edge_coverage(&tri, (234, 357)));
+    assert!(!edge_coverage(&tri, (236, 277)));
 
-    if naive_point_in_triangle(point, triangle) {
-        return true;
+    tri.clear();
+    tri.push(Vector2::<f32>::new(375.0, 186.0));
+    tri.push(Vector2::<f32>::new(2.0, 257.0));
+    tri.push(Vector2::<f32>::new(483.0, 5.0));
+
+    assert!(edge_coverage(&tri, (340, 110)));
+    assert!(!edge_coverage(&tri, (288, 82)));
+    assert!(edge_coverage(&tri, (350, 150)));
+}
+
+
+/// Per-row edge-function state, carried in fixed-point subpixel space: a value per edge
+/// sampled at pixel centers, plus the constant x/y steps (one pixel's worth of subpixel units)
+/// needed to walk it across the bounding box without recomputing the cross products at every
+/// pixel.
+struct EdgeWalk {
+    area: i64,
+    w0_row: i64,
+    w1_row: i64,
+    w2_row: i64,
+    step_x0: i64,
+    step_x1: i64,
+    step_x2: i64,
+    step_y0: i64,
+    step_y1: i64,
+    step_y2: i64,
+    top_left0: bool,
+    top_left1: bool,
+    top_left2: bool,
+    /// Whether the second and third vertices were swapped to make the area positive.
+    swapped: bool,
+}
+
+impl EdgeWalk {
+    /// Sets up the incremental edge functions for `triangle` (screen-space, un-rounded),
+    /// sampled at the pixel centers of bounding-box row `origin_y`, starting at column
+    /// `origin_x`. Returns `None` for degenerate (zero-area) triangles.
+    fn new(triangle: &[Vector2<f32>], origin_x: i32, origin_y: i32) -> Option<EdgeWalk> {
+        let a = snap_vertex(triangle[0]);
+        let b = snap_vertex(triangle[1]);
+        let c = snap_vertex(triangle[2]);
+
+        let area = edge_function(a, b, c);
+        if area == 0 {
+            return None;
+        }
+        let swapped = area < 0;
+        let (a, b, c) = if swapped { (a, c, b) } else { (a, b, c) };
+        let area = edge_function(a, b, c);
+
+        let origin = Vector2::new(
+            origin_x * SUBPIXEL_SCALE + SUBPIXEL_SCALE / 2,
+            origin_y * SUBPIXEL_SCALE + SUBPIXEL_SCALE / 2,
+        );
+        let scale = SUBPIXEL_SCALE as i64;
+
+        Some(EdgeWalk {
+            area: area,
+            w0_row: edge_function(a, b, origin),
+            w1_row: edge_function(b, c, origin),
+            w2_row: edge_function(c, a, origin),
+            step_x0: (b.y - a.y) as i64 * scale,
+            step_x1: (c.y - b.y) as i64 * scale,
+            step_x2: (a.y - c.y) as i64 * scale,
+            step_y0: (a.x - b.x) as i64 * scale,
+            step_y1: (b.x - c.x) as i64 * scale,
+            step_y2: (c.x - a.x) as i64 * scale,
+            top_left0: is_top_left_edge(a, b),
+            top_left1: is_top_left_edge(b, c),
+            top_left2: is_top_left_edge(c, a),
+            swapped: swapped,
+        })
     }
-    if distance_square_point_to_segment(x1, y1, x2, y2, x, y) <= EPSILON_SQUARE {
-        return true;
+
+    fn next_row(&mut self) {
+        self.w0_row += self.step_y0;
+        self.w1_row += self.step_y1;
+        self.w2_row += self.step_y2;
     }
-    if distance_square_point_to_segment(x2, y2, x3, y3, x, y) <= EPSILON_SQUARE {
-        return true;
+
+    /// Barycentric weights for the current edge values, in the vertex order of the triangle
+    /// passed to `new` (the internal winding swap is undone), suitable for attribute
+    /// interpolation.
+    fn weights(&self, w0: i64, w1: i64, w2: i64) -> Vector3<f32> {
+        let (wb, wc) = if self.swapped { (w0, w2) } else { (w2, w0) };
+        Vector3::new(
+            w1 as f32 / self.area as f32,
+            wb as f32 / self.area as f32,
+            wc as f32 / self.area as f32,
+        )
     }
-    if distance_square_point_to_segment(x3, y3, x1, y1, x, y) <= EPSILON_SQUARE {
-        return true;
+
+    fn covered(&self, w0: i64, w1: i64, w2: i64) -> bool {
+        (w0 > 0 || (w0 == 0 && self.top_left0)) && (w1 > 0 || (w1 == 0 && self.top_left1)) &&
+            (w2 > 0 || (w2 == 0 && self.top_left2))
     }
-    false
 }
 
-#[test]
-fn test_point_in_triangle() {
-    let mut tri: Vec<Vector2<u32>> = Vec::with_capacity(3);
-    tri.push(Vector2::<u32>::new(245, 391));
-    tri.push(Vector2::<u32>::new(115, 200));
-    tri.push(Vector2::<u32>::new(306, 438));
-
-    let mut point = (234, 357);
-    assert!(point_in_triangle(point, tri.as_ref()));
-    point = (236, 277);
-    assert!(!point_in_triangle(point, tri.as_ref()));
 
-    tri.clear();
-    tri.push(Vector2::<u32>::new(375, 186));
-    tri.push(Vector2::<u32>::new(2, 257));
-    tri.push(Vector2::<u32>::new(483, 5));
+/// Tile size (in pixels) used for the trivial accept/reject traversal in `draw`.
+const TILE_SIZE: i32 = 8;
 
-    point = (340, 110);
-    assert!(point_in_triangle(point, tri.as_ref()));
-    point = (288, 82);
-    assert!(!point_in_triangle(point, tri.as_ref()));
-    point = (375, 186);
-    assert!(point_in_triangle(point, tri.as_ref()));
+/// Value of an edge function at whichever corner of a `tile_w` x `tile_h` tile is most extreme
+/// in the direction of the edge's gradient: `accept = true` gives the corner where the edge
+/// function is largest (the tile's best case, used to prove a tile can't be trivially rejected),
+/// `accept = false` gives the corner where it's smallest (the tile's worst case, used to prove a
+/// tile is trivially accepted). `w` is the edge value already evaluated at the tile's min corner.
+fn tile_corner_value(w: i64, step_x: i64, step_y: i64, tile_w: i32, tile_h: i32, accept: bool) -> i64 {
+    let dx = if (step_x > 0) == accept { step_x * (tile_w - 1) as i64 } else { 0 };
+    let dy = if (step_y > 0) == accept { step_y * (tile_h - 1) as i64 } else { 0 };
+    w + dx + dy
 }
 
-
-/// Draw triangle from given vertex positions.
+/// Draw triangle from given (un-rounded) vertex screen positions. `scissor`, if set, is
+/// `(min_x, min_y, max_x, max_y)` and is intersected with the triangle's bounding box before
+/// traversal, same as the framebuffer bounds.
+///
+/// Traverses the bounding box in `TILE_SIZE` x `TILE_SIZE` tiles. A tile is skipped outright if
+/// its "reject corner" fails any edge, filled without per-pixel tests if its "accept corner"
+/// passes all three, and otherwise falls back to the exact per-pixel edge test.
 pub fn draw(
-    triangle: &[Vector2<u32>],
+    triangle: &[Vector2<f32>],
     color: color::Color,
     buffer: &mut [u32],
     buffer_width: usize,
+    buffer_height: usize,
+    scissor: Option<(u32, u32, u32, u32)>,
 ) {
+    let snapped: Vec<Vector2<i32>> = triangle.iter().cloned().map(snap_vertex).collect();
+    let bb = bounding_box(&snapped);
+    let (bb_min_x, bb_min_y, bb_max_x, bb_max_y) =
+        clip_bounding_box(bb, buffer_width as u32, buffer_height as u32, scissor);
+    if bb_min_x >= bb_max_x || bb_min_y >= bb_max_y {
+        return;
+    }
+
+    let walk = match EdgeWalk::new(triangle, bb_min_x, bb_min_y) {
+        Some(walk) => walk,
+        None => return,
+    };
+
+    let mut row_w0 = walk.w0_row;
+    let mut row_w1 = walk.w1_row;
+    let mut row_w2 = walk.w2_row;
+
+    let mut tile_y = bb_min_y;
+    while tile_y < bb_max_y {
+        let tile_h = TILE_SIZE.min(bb_max_y - tile_y);
+
+        let mut col_w0 = row_w0;
+        let mut col_w1 = row_w1;
+        let mut col_w2 = row_w2;
+
+        let mut tile_x = bb_min_x;
+        while tile_x < bb_max_x {
+            let tile_w = TILE_SIZE.min(bb_max_x - tile_x);
+
+            let reject0 = tile_corner_value(col_w0, walk.step_x0, walk.step_y0, tile_w, tile_h, true);
+            let reject1 = tile_corner_value(col_w1, walk.step_x1, walk.step_y1, tile_w, tile_h, true);
+            let reject2 = tile_corner_value(col_w2, walk.step_x2, walk.step_y2, tile_w, tile_h, true);
+
+            if reject0 < 0 || reject1 < 0 || reject2 < 0 {
+                // Trivial reject: the whole tile is outside the triangle.
+            } else {
+                let accept0 = tile_corner_value(col_w0, walk.step_x0, walk.step_y0, tile_w, tile_h, false);
+                let accept1 = tile_corner_value(col_w1, walk.step_x1, walk.step_y1, tile_w, tile_h, false);
+                let accept2 = tile_corner_value(col_w2, walk.step_x2, walk.step_y2, tile_w, tile_h, false);
+
+                if walk.covered(accept0, accept1, accept2) {
+                    // Trivial accept: the whole tile is inside, fill it without testing pixels.
+                    for y in tile_y..(tile_y + tile_h) {
+                        for x in tile_x..(tile_x + tile_w) {
+                            buffer[utils::xy(x as usize, y as usize, buffer_width)] = color.bgra();
+                        }
+                    }
+                } else {
+                    // Partially covered: fall back to the exact per-pixel edge test.
+                    let mut w0 = col_w0;
+                    let mut w1 = col_w1;
+                    let mut w2 = col_w2;
+                    for y in tile_y..(tile_y + tile_h) {
+                        let mut pw0 = w0;
+                        let mut pw1 = w1;
+                        let mut pw2 = w2;
+                        for x in tile_x..(tile_x + tile_w) {
+                            if walk.covered(pw0, pw1, pw2) {
+                                buffer[utils::xy(x as usize, y as usize, buffer_width)] = color.bgra();
+                            }
+                            pw0 += walk.step_x0;
+                            pw1 += walk.step_x1;
+                            pw2 += walk.step_x2;
+                        }
+                        w0 += walk.step_y0;
+                        w1 += walk.step_y1;
+                        w2 += walk.step_y2;
+                    }
+                }
+            }
+
+            col_w0 += walk.step_x0 * tile_w as i64;
+            col_w1 += walk.step_x1 * tile_w as i64;
+            col_w2 += walk.step_x2 * tile_w as i64;
+            tile_x += tile_w;
+        }
+
+        row_w0 += walk.step_y0 * tile_h as i64;
+        row_w1 += walk.step_y1 * tile_h as i64;
+        row_w2 += walk.step_y2 * tile_h as i64;
+        tile_y += tile_h;
+    }
+}
+
+/// The tiled traversal in `draw` must cover exactly the same pixels as the non-tiled
+/// `edge_coverage` reference, for triangles whose bounding box spans several tile boundaries
+/// in both directions.
+#[test]
+fn test_draw_matches_edge_coverage_reference() {
+    let buffer_width = 32usize;
+    let buffer_height = 32usize;
+    let triangles = [
+        [
+            Vector2::<f32>::new(3.0, 4.0),
+            Vector2::<f32>::new(28.0, 9.0),
+            Vector2::<f32>::new(12.0, 27.0),
+        ],
+        [
+            Vector2::<f32>::new(0.0, 0.0),
+            Vector2::<f32>::new(31.0, 5.0),
+            Vector2::<f32>::new(5.0, 31.0),
+        ],
+        [
+            Vector2::<f32>::new(1.0, 31.0),
+            Vector2::<f32>::new(31.0, 1.0),
+            Vector2::<f32>::new(16.0, 16.0),
+        ],
+    ];
 
-    let (bb_min_x, bb_min_y, bb_max_x, bb_max_y) = bounding_box(triangle);
+    for tri in &triangles {
+        let mut buffer = vec![0u32; buffer_width * buffer_height];
+        draw(
+            tri,
+            color::Color::red(),
+            &mut buffer,
+            buffer_width,
+            buffer_height,
+            None,
+        );
 
-    for y in bb_min_y..(bb_max_y) {
-        let line = line::LineIterator::new(bb_min_x, y, bb_max_x, y);
-        for point in line.filter(|p| point_in_triangle(*p, triangle)) {
-            buffer[utils::xy(point.0, point.1, buffer_width)] = color.bgra();
+        for y in 0..buffer_height as u32 {
+            for x in 0..buffer_width as u32 {
+                let expected = edge_coverage(tri, (x, y));
+                let drawn = buffer[utils::xy(x as usize, y as usize, buffer_width)] != 0;
+                assert_eq!(
+                    expected,
+                    drawn,
+                    "mismatch at ({}, {}) for triangle {:?}",
+                    x,
+                    y,
+                    tri
+                );
+            }
         }
     }
 }
 
 
-pub struct TriangleIterator<'a> {
-    bb_min_x: u32,
-    bb_max_x: u32,
-    bb_max_y: u32,
-    triangle: &'a [Vector2<u32>],
-    y: u32,
+/// Per-pixel row walk yielding covered pixels with their barycentric weights, for consumers
+/// (`Gl::draw`) that need to interpolate attributes at every pixel. Unlike `draw`'s tiled
+/// traversal, this doesn't trivially-accept whole tiles: a tile fill can't skip the per-pixel
+/// edge test without also skipping the per-pixel weights it would otherwise hand back.
+pub struct TriangleIterator {
+    bb_min_x: i32,
+    bb_max_x: i32,
+    bb_max_y: i32,
+    walk: Option<EdgeWalk>,
+    y: i32,
 }
 
-impl<'a> TriangleIterator<'a> {
-    pub fn new(triangle: &'a [Vector2<u32>]) -> TriangleIterator {
-        let (bb_min_x, bb_min_y, bb_max_x, bb_max_y) = bounding_box(triangle);
+impl TriangleIterator {
+    pub fn new(
+        triangle: &[Vector2<f32>],
+        buffer_width: u32,
+        buffer_height: u32,
+        scissor: Option<(u32, u32, u32, u32)>,
+    ) -> TriangleIterator {
+        let snapped: Vec<Vector2<i32>> = triangle.iter().cloned().map(snap_vertex).collect();
+        let bb = bounding_box(&snapped);
+        let (bb_min_x, bb_min_y, bb_max_x, bb_max_y) =
+            clip_bounding_box(bb, buffer_width, buffer_height, scissor);
+        let walk = if bb_min_x < bb_max_x && bb_min_y < bb_max_y {
+            EdgeWalk::new(triangle, bb_min_x, bb_min_y)
+        } else {
+            None
+        };
         TriangleIterator {
             bb_min_x: bb_min_x,
             bb_max_x: bb_max_x,
             bb_max_y: bb_max_y,
-            triangle: triangle,
+            walk: walk,
             y: bb_min_y,
         }
     }
 }
 
-impl<'a> Iterator for TriangleIterator<'a> {
-    type Item = Vec<(usize, usize)>;
+impl Iterator for TriangleIterator {
+    /// The pixels of one bounding-box row covered by the triangle, each with its barycentric
+    /// weights for attribute interpolation (free by-product of the edge-function test).
+    type Item = Vec<(usize, usize, Vector3<f32>)>;
 
-    fn next(&mut self) -> Option<Vec<(usize, usize)>> {
-        if self.y > self.bb_max_y {
+    fn next(&mut self) -> Option<Vec<(usize, usize, Vector3<f32>)>> {
+        if self.y >= self.bb_max_y {
             return None;
         }
+        let row = {
+            let walk = match self.walk {
+                Some(ref walk) => walk,
+                None => return None,
+            };
+            let mut w0 = walk.w0_row;
+            let mut w1 = walk.w1_row;
+            let mut w2 = walk.w2_row;
+            let mut row = Vec::new();
+            for x in self.bb_min_x..self.bb_max_x {
+                if walk.covered(w0, w1, w2) {
+                    row.push((x as usize, self.y as usize, walk.weights(w0, w1, w2)));
+                }
+                w0 += walk.step_x0;
+                w1 += walk.step_x1;
+                w2 += walk.step_x2;
+            }
+            row
+        };
+        if let Some(ref mut walk) = self.walk {
+            walk.next_row();
+        }
         self.y += 1;
-        Some(
-            line::LineIterator::new(self.bb_min_x, self.y, self.bb_max_x, self.y)
-                .filter(|p| point_in_triangle(*p, self.triangle))
-                .collect(),
-        )
+        Some(row)
+    }
+}
+
+/// A triangle whose bounding box reaches the last row/column of the framebuffer must not
+/// yield a pixel at `y == buffer_height` (or `x == buffer_width`): `bb_max_y`/`bb_max_x` are
+/// exclusive bounds, matching `clip_bounding_box`'s clamp to the framebuffer size, so indexing
+/// `self.framebuffer[utils::xy(x, buffer_height, buffer_width)]` in a consumer like `Gl::draw`
+/// would otherwise panic.
+#[test]
+fn test_triangle_iterator_stays_in_bounds_at_framebuffer_edge() {
+    let buffer_width = 16u32;
+    let buffer_height = 16u32;
+    let tri = [
+        Vector2::<f32>::new(0.0, 8.0),
+        Vector2::<f32>::new(15.0, 8.0),
+        Vector2::<f32>::new(8.0, 20.0),
+    ];
+
+    let iter = TriangleIterator::new(&tri, buffer_width, buffer_height, None);
+    for row in iter {
+        for (x, y, _) in row {
+            assert!(x < buffer_width as usize);
+            assert!(y < buffer_height as usize);
+        }
+    }
+}
+
+/// `draw`'s scissor rectangle, the feature this request actually added, must keep every pixel
+/// outside it untouched, even though the triangle itself extends past it on every side.
+#[test]
+fn test_draw_respects_scissor() {
+    let buffer_width = 32usize;
+    let buffer_height = 32usize;
+    let tri = [
+        Vector2::<f32>::new(0.0, 0.0),
+        Vector2::<f32>::new(31.0, 0.0),
+        Vector2::<f32>::new(0.0, 31.0),
+    ];
+    let scissor = Some((4u32, 4u32, 20u32, 20u32));
+
+    let mut buffer = vec![0u32; buffer_width * buffer_height];
+    draw(
+        &tri,
+        color::Color::red(),
+        &mut buffer,
+        buffer_width,
+        buffer_height,
+        scissor,
+    );
+
+    let mut any_drawn = false;
+    for y in 0..buffer_height as u32 {
+        for x in 0..buffer_width as u32 {
+            let drawn = buffer[utils::xy(x as usize, y as usize, buffer_width)] != 0;
+            if drawn {
+                any_drawn = true;
+                assert!(
+                    x >= 4 && x < 20 && y >= 4 && y < 20,
+                    "pixel ({}, {}) drawn outside the scissor rect",
+                    x,
+                    y
+                );
+            }
+        }
     }
+    assert!(any_drawn, "nothing was drawn inside the scissor rect either");
 }